@@ -39,48 +39,108 @@ use std::io;
 use std::path::Path;
 use std::path::PathBuf;
 
-enum Mode {
-    DirectoryBased(PathBuf),
-    ManifestBased(HashMap<PathBuf, PathBuf>),
-}
-
 pub struct Runfiles {
-    mode: Mode,
+    // If a runfiles manifest was discovered, this holds the path -> path
+    // mapping it contains.
+    manifest: Option<HashMap<PathBuf, PathBuf>>,
+    // The location of the manifest file backing `manifest`, retained so it
+    // can be propagated to child processes via `env_vars()`.
+    manifest_path: Option<PathBuf>,
+    // If a runfiles directory is present, this holds its location.
+    directory: Option<PathBuf>,
+    // Maps (source canonical repo, apparent repo name) -> target canonical
+    // repo, as parsed from the runfiles tree's `_repo_mapping` file. Empty
+    // if no such file was found, which is the case for binaries built
+    // without bzlmod.
+    repo_mapping: HashMap<(String, String), String>,
 }
 
 impl Runfiles {
-    /// Creates a manifest based Runfiles object when
-    /// RUNFILES_MANIFEST_ONLY environment variable is present,
-    /// or a directory based Runfiles object otherwise.
+    /// Creates a new `Runfiles` object by locating whichever of a runfiles
+    /// manifest and a runfiles directory are available to the current
+    /// binary. At least one of the two must be found, but unlike the
+    /// upstream C++/Java/Python implementations, Rust binaries that have
+    /// both use both: a lookup the manifest misses falls back to the
+    /// directory.
     pub fn create() -> io::Result<Self> {
-        if is_manifest_only() {
-            Self::create_manifest_based()
+        Self::create_for(&current_binary_path())
+    }
+
+    fn create_for(binary_path: &Path) -> io::Result<Self> {
+        let candidate_manifest_path = find_manifest_path_for(binary_path);
+        let manifest = candidate_manifest_path
+            .clone()
+            .and_then(|path| Self::parse_manifest(path).ok());
+        // Only keep the manifest path around (e.g. for `env_vars()`) if it
+        // actually parsed; a stale or unreadable path left over from an
+        // unrelated parent process shouldn't prevent falling back to a
+        // runfiles directory.
+        let manifest_path = if manifest.is_some() {
+            candidate_manifest_path
         } else {
-            Self::create_directory_based()
+            None
+        };
+        let directory = find_runfiles_dir_for(binary_path).ok();
+
+        if manifest.is_none() && directory.is_none() {
+            return Err(make_io_error(
+                "failed to find a runfiles manifest or a runfiles directory",
+            ));
         }
-    }
 
-    fn create_directory_based() -> io::Result<Self> {
+        let repo_mapping = Self::parse_repo_mapping(&manifest, &directory);
+
         Ok(Runfiles {
-            mode: Mode::DirectoryBased(find_runfiles_dir()?),
+            manifest,
+            manifest_path,
+            directory,
+            repo_mapping,
         })
     }
 
-    fn create_manifest_based() -> io::Result<Self> {
-        let manifest_path = find_manifest_path()?;
+    fn parse_manifest(manifest_path: PathBuf) -> io::Result<HashMap<PathBuf, PathBuf>> {
         let manifest_content = std::fs::read_to_string(manifest_path)?;
-        let path_mapping = manifest_content
+        manifest_content
             .lines()
             .map(|line| {
-                let pair = line
-                    .split_once(" ")
-                    .expect("manifest file contained unexpected content");
-                (pair.0.into(), pair.1.into())
+                line.split_once(" ")
+                    .map(|(a, b)| (PathBuf::from(a), PathBuf::from(b)))
+                    .ok_or_else(|| make_io_error("manifest file contained unexpected content"))
             })
-            .collect::<HashMap<_, _>>();
-        Ok(Runfiles {
-            mode: Mode::ManifestBased(path_mapping),
-        })
+            .collect::<io::Result<HashMap<_, _>>>()
+    }
+
+    /// Locates and parses the `_repo_mapping` file Bazel writes at the root
+    /// of the runfiles tree under bzlmod. Returns an empty mapping if the
+    /// file can't be found, which is expected for binaries built without
+    /// bzlmod (e.g. plain WORKSPACE builds).
+    fn parse_repo_mapping(
+        manifest: &Option<HashMap<PathBuf, PathBuf>>,
+        directory: &Option<PathBuf>,
+    ) -> HashMap<(String, String), String> {
+        let repo_mapping_path = manifest
+            .as_ref()
+            .and_then(|m| m.get(Path::new("_repo_mapping")).cloned())
+            .or_else(|| directory.as_ref().map(|dir| dir.join("_repo_mapping")));
+
+        let content = match repo_mapping_path.and_then(|path| fs::read_to_string(path).ok()) {
+            Some(content) => content,
+            None => return HashMap::new(),
+        };
+
+        content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, ',');
+                let source_repo = fields.next()?;
+                let apparent_name = fields.next()?;
+                let canonical_repo = fields.next()?;
+                Some((
+                    (source_repo.to_owned(), apparent_name.to_owned()),
+                    canonical_repo.to_owned(),
+                ))
+            })
+            .collect()
     }
 
     /// Returns the runtime path of a runfile.
@@ -88,40 +148,140 @@ impl Runfiles {
     /// Runfiles are data-dependencies of Bazel-built binaries and tests.
     /// The returned path may not be valid. The caller should check the path's
     /// validity and that the path exists.
+    ///
+    /// When both a manifest and a runfiles directory are available, the
+    /// manifest is consulted first and the directory is used as a fallback
+    /// for any path it doesn't list. A path resolved against the directory is
+    /// joined onto it without checking that it exists, so the directory is
+    /// never followed by a second manifest lookup.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` is not found among the runfiles. Use
+    /// [`Runfiles::try_rlocation`] to handle a missing path as a recoverable
+    /// error instead.
     pub fn rlocation(&self, path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        self.try_rlocation(path)
+            .unwrap_or_else(|_| panic!("Path {} not found among runfiles.", path.to_string_lossy()))
+    }
+
+    /// Like [`Runfiles::rlocation`], but returns a
+    /// [`NotFound`](io::ErrorKind::NotFound) error instead of panicking when
+    /// `path` is absent from the manifest and can't be resolved against a
+    /// runfiles directory either.
+    pub fn try_rlocation(&self, path: impl AsRef<Path>) -> io::Result<PathBuf> {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return Ok(path.to_path_buf());
+        }
+
+        if let Some(mapped) = self.manifest.as_ref().and_then(|m| m.get(path)) {
+            return Ok(mapped.clone());
+        }
+
+        if let Some(runfiles_dir) = &self.directory {
+            return Ok(runfiles_dir.join(path));
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Path {} not found among runfiles.", path.to_string_lossy()),
+        ))
+    }
+
+    /// Returns the runtime path of a runfile, resolving its leading
+    /// workspace-relative path component through the repository mapping of
+    /// `source_repo` (the canonical name of the repo doing the lookup).
+    ///
+    /// This allows callers to use the "apparent" repository name they
+    /// depend on (as written in their own `BUILD` file) even when the
+    /// runfiles tree was laid out using bzlmod's canonical repository
+    /// names, which commonly differ.
+    pub fn rlocation_from(&self, path: impl AsRef<Path>, source_repo: &str) -> PathBuf {
         let path = path.as_ref();
         if path.is_absolute() {
             return path.to_path_buf();
         }
-        match &self.mode {
-            Mode::DirectoryBased(runfiles_dir) => runfiles_dir.join(path),
-            Mode::ManifestBased(path_mapping) => path_mapping
-                .get(path)
-                .expect(&format!(
-                    "Path {} not found among runfiles.",
-                    path.to_string_lossy()
-                ))
-                .clone(),
+
+        let mut components = path.components();
+        if let Some(std::path::Component::Normal(apparent_repo)) = components.next() {
+            let key = (source_repo.to_owned(), apparent_repo.to_string_lossy().into_owned());
+            if let Some(canonical_repo) = self.repo_mapping.get(&key) {
+                let remapped = Path::new(canonical_repo).join(components.as_path());
+                return self.rlocation(remapped);
+            }
+        }
+
+        self.rlocation(path)
+    }
+
+    /// Returns the environment variables that should be set for a child
+    /// process so that it can find this binary's runfiles, e.g. when this
+    /// binary spawns another runfiles-consuming binary as a data dependency.
+    pub fn env_vars(&self) -> Vec<(OsString, OsString)> {
+        let mut vars = Vec::new();
+        if let Some(directory) = &self.directory {
+            vars.push((OsString::from("RUNFILES_DIR"), directory.clone().into()));
+        }
+        if let Some(manifest_path) = &self.manifest_path {
+            vars.push((
+                OsString::from("RUNFILES_MANIFEST_FILE"),
+                manifest_path.clone().into(),
+            ));
+            vars.push((OsString::from("RUNFILES_MANIFEST_ONLY"), OsString::from("1")));
         }
+        vars
     }
 }
 
 /// Returns the .runfiles directory for the currently executing binary.
+///
+/// Discovery is attempted in the order Bazel's launchers use: a `.runfiles`
+/// directory owned by this binary, then the `RUNFILES_DIR` environment
+/// variable a parent process may have set, then a walk of the `argv[0]`
+/// symlink chain (needed under remote execution, where the binary can end
+/// up nested inside its own runfiles tree without a direct sibling).
 pub fn find_runfiles_dir() -> io::Result<PathBuf> {
-    assert_ne!(
-        std::env::var_os("RUNFILES_MANIFEST_ONLY").unwrap_or(OsString::from("0")),
-        "1"
-    );
-    let exec_path = std::env::args().nth(0).expect("arg 0 was not set");
+    find_runfiles_dir_for(&current_binary_path())
+}
 
-    let mut binary_path = PathBuf::from(&exec_path);
-    loop {
-        // Check for our neighboring $binary.runfiles directory.
-        let mut runfiles_name = binary_path.file_name().unwrap().to_owned();
-        runfiles_name.push(".runfiles");
+/// Returns the path of the currently executing binary, as seen through
+/// `argv[0]`.
+fn current_binary_path() -> PathBuf {
+    PathBuf::from(std::env::args().nth(0).expect("arg 0 was not set"))
+}
+
+fn find_runfiles_dir_for(binary_path: &Path) -> io::Result<PathBuf> {
+    if let Some(runfiles_dir) = sibling_runfiles_dir(binary_path) {
+        return Ok(runfiles_dir);
+    }
 
-        let runfiles_path = binary_path.with_file_name(&runfiles_name);
-        if runfiles_path.is_dir() {
+    if let Some(runfiles_dir) = std::env::var_os("RUNFILES_DIR") {
+        return Ok(PathBuf::from(runfiles_dir));
+    }
+
+    walk_for_runfiles_dir(binary_path.to_path_buf())
+}
+
+/// Returns `<binary_path>.runfiles` if that directory exists.
+fn sibling_runfiles_dir(binary_path: &Path) -> Option<PathBuf> {
+    let mut runfiles_name = binary_path.file_name()?.to_owned();
+    runfiles_name.push(".runfiles");
+
+    let runfiles_path = binary_path.with_file_name(&runfiles_name);
+    if runfiles_path.is_dir() {
+        Some(runfiles_path)
+    } else {
+        None
+    }
+}
+
+/// Falls back to walking the `argv[0]` symlink chain, looking for a
+/// neighboring `.runfiles` directory or an ancestor that is itself one.
+fn walk_for_runfiles_dir(mut binary_path: PathBuf) -> io::Result<PathBuf> {
+    loop {
+        if let Some(runfiles_path) = sibling_runfiles_dir(&binary_path) {
             return Ok(runfiles_path);
         }
 
@@ -160,24 +320,32 @@ fn make_io_error(msg: &str) -> io::Error {
     io::Error::new(io::ErrorKind::Other, msg)
 }
 
-fn is_manifest_only() -> bool {
-    match std::env::var("RUNFILES_MANIFEST_ONLY") {
-        Ok(val) => val == "1",
-        Err(_) => false,
+/// Locates the runfiles manifest for the currently executing binary, trying,
+/// in order: a sibling `<argv0>.runfiles_manifest` file or a `MANIFEST` file
+/// inside the sibling `<argv0>.runfiles` directory, then the
+/// `RUNFILES_MANIFEST_FILE` environment variable a parent process may have
+/// set. Returns `None` if none of these names an existing file, which is the
+/// common case for directory-based runfiles.
+fn find_manifest_path_for(binary_path: &Path) -> Option<PathBuf> {
+    if let Some(manifest_name) = binary_path.file_name() {
+        let mut manifest_name = manifest_name.to_owned();
+        manifest_name.push(".runfiles_manifest");
+        let sibling_manifest = binary_path.with_file_name(&manifest_name);
+        if sibling_manifest.is_file() {
+            return Some(sibling_manifest);
+        }
     }
-}
 
-fn find_manifest_path() -> io::Result<PathBuf> {
-    assert_eq!(
-        std::env::var_os("RUNFILES_MANIFEST_ONLY").expect("RUNFILES_MANIFEST_ONLY was not set"),
-        OsString::from("1")
-    );
-    match std::env::var_os("RUNFILES_MANIFEST_FILE") {
-        Some(path) => Ok(path.into()),
-        None => Err(
-            make_io_error(
-                "RUNFILES_MANIFEST_ONLY was set to '1', but RUNFILES_MANIFEST_FILE was not set. Did Bazel change?"))
+    if let Some(runfiles_dir) = sibling_runfiles_dir(binary_path) {
+        let manifest = runfiles_dir.join("MANIFEST");
+        if manifest.is_file() {
+            return Some(manifest);
+        }
     }
+
+    std::env::var_os("RUNFILES_MANIFEST_FILE")
+        .map(PathBuf::from)
+        .filter(|path| path.is_file())
 }
 
 #[cfg(test)]
@@ -186,9 +354,31 @@ mod test {
 
     use std::fs::File;
     use std::io::prelude::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    // `RUNFILES_DIR`/`RUNFILES_MANIFEST_FILE` are process-global, so tests
+    // that set them must not run concurrently with each other or with
+    // `test_can_read_data_from_runfiles`, which relies on the ambient
+    // environment a real `bazel test` invocation provides.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn scratch_dir(label: &str) -> PathBuf {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "runfiles_test_{}_{}_{}",
+            std::process::id(),
+            label,
+            n
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
 
     #[test]
     fn test_can_read_data_from_runfiles() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
         let r = Runfiles::create().unwrap();
 
         let mut f = File::open(r.rlocation("rules_rust/tools/runfiles/data/sample.txt")).unwrap();
@@ -204,9 +394,233 @@ mod test {
         let mut path_mapping = HashMap::new();
         path_mapping.insert("a/b".into(), "c/d".into());
         let r = Runfiles {
-            mode: Mode::ManifestBased(path_mapping),
+            manifest: Some(path_mapping),
+            manifest_path: None,
+            directory: None,
+            repo_mapping: HashMap::new(),
         };
 
         assert_eq!(r.rlocation("a/b"), PathBuf::from("c/d"));
     }
+
+    #[test]
+    fn test_try_rlocation_returns_err_for_missing_manifest_entry() {
+        let r = Runfiles {
+            manifest: Some(HashMap::new()),
+            manifest_path: None,
+            directory: None,
+            repo_mapping: HashMap::new(),
+        };
+
+        assert_eq!(
+            r.try_rlocation("a/b").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn test_rlocation_from_resolves_apparent_repo_name() {
+        let mut path_mapping = HashMap::new();
+        path_mapping.insert("canonical_dep/data.txt".into(), "c/d".into());
+        let mut repo_mapping = HashMap::new();
+        repo_mapping.insert(
+            ("my_repo".to_owned(), "dep".to_owned()),
+            "canonical_dep".to_owned(),
+        );
+        let r = Runfiles {
+            manifest: Some(path_mapping),
+            manifest_path: None,
+            directory: None,
+            repo_mapping,
+        };
+
+        assert_eq!(
+            r.rlocation_from("dep/data.txt", "my_repo"),
+            PathBuf::from("c/d")
+        );
+    }
+
+    #[test]
+    fn test_create_for_parses_repo_mapping_from_runfiles_directory() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = scratch_dir("create_repo_mapping");
+        let binary_path = dir.join("my_binary");
+        File::create(&binary_path).unwrap();
+        let mut runfiles_name = binary_path.file_name().unwrap().to_owned();
+        runfiles_name.push(".runfiles");
+        let runfiles_dir = binary_path.with_file_name(&runfiles_name);
+        fs::create_dir_all(&runfiles_dir).unwrap();
+
+        let mut f = File::create(runfiles_dir.join("_repo_mapping")).unwrap();
+        writeln!(f, "my_repo,dep,canonical_dep~1.2.3").unwrap();
+        writeln!(f, ",other_dep,canonical_other~4.5.6").unwrap();
+
+        let r = Runfiles::create_for(&binary_path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            r.repo_mapping
+                .get(&("my_repo".to_owned(), "dep".to_owned())),
+            Some(&"canonical_dep~1.2.3".to_owned())
+        );
+        assert_eq!(
+            r.repo_mapping
+                .get(&(String::new(), "other_dep".to_owned())),
+            Some(&"canonical_other~4.5.6".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_env_vars_directory_based() {
+        let r = Runfiles {
+            manifest: None,
+            manifest_path: None,
+            directory: Some(PathBuf::from("runfiles_dir")),
+            repo_mapping: HashMap::new(),
+        };
+
+        assert_eq!(
+            r.env_vars(),
+            vec![(
+                OsString::from("RUNFILES_DIR"),
+                OsString::from("runfiles_dir")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_env_vars_manifest_based() {
+        let r = Runfiles {
+            manifest: Some(HashMap::new()),
+            manifest_path: Some(PathBuf::from("manifest_file")),
+            directory: None,
+            repo_mapping: HashMap::new(),
+        };
+
+        assert_eq!(
+            r.env_vars(),
+            vec![
+                (
+                    OsString::from("RUNFILES_MANIFEST_FILE"),
+                    OsString::from("manifest_file")
+                ),
+                (
+                    OsString::from("RUNFILES_MANIFEST_ONLY"),
+                    OsString::from("1")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_manifest_path_prefers_sibling_file_over_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = scratch_dir("sibling_manifest");
+        let binary_path = dir.join("my_binary");
+        File::create(&binary_path).unwrap();
+        let sibling_manifest = dir.join("my_binary.runfiles_manifest");
+        File::create(&sibling_manifest).unwrap();
+
+        unsafe { std::env::set_var("RUNFILES_MANIFEST_FILE", "/nonexistent/stale/MANIFEST") };
+        let found = find_manifest_path_for(&binary_path);
+        unsafe { std::env::remove_var("RUNFILES_MANIFEST_FILE") };
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found, Some(sibling_manifest));
+    }
+
+    #[test]
+    fn test_find_manifest_path_falls_back_to_env_var_when_it_points_at_a_real_file() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = scratch_dir("env_manifest");
+        let binary_path = dir.join("my_binary");
+        File::create(&binary_path).unwrap();
+        let manifest_file = dir.join("MANIFEST_FILE");
+        File::create(&manifest_file).unwrap();
+
+        unsafe { std::env::set_var("RUNFILES_MANIFEST_FILE", &manifest_file) };
+        let found = find_manifest_path_for(&binary_path);
+        unsafe { std::env::remove_var("RUNFILES_MANIFEST_FILE") };
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found, Some(manifest_file));
+    }
+
+    #[test]
+    fn test_find_manifest_path_ignores_stale_env_var() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = scratch_dir("stale_manifest");
+        let binary_path = dir.join("my_binary");
+        File::create(&binary_path).unwrap();
+
+        unsafe { std::env::set_var("RUNFILES_MANIFEST_FILE", "/nonexistent/stale/MANIFEST") };
+        let found = find_manifest_path_for(&binary_path);
+        unsafe { std::env::remove_var("RUNFILES_MANIFEST_FILE") };
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found, None);
+    }
+
+    #[test]
+    fn test_create_for_falls_back_to_directory_when_manifest_env_var_is_stale() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = scratch_dir("create_fallback");
+        let binary_path = dir.join("my_binary");
+        File::create(&binary_path).unwrap();
+        let mut runfiles_name = binary_path.file_name().unwrap().to_owned();
+        runfiles_name.push(".runfiles");
+        let runfiles_dir = binary_path.with_file_name(&runfiles_name);
+        fs::create_dir_all(&runfiles_dir).unwrap();
+
+        unsafe { std::env::set_var("RUNFILES_MANIFEST_FILE", "/nonexistent/stale/MANIFEST") };
+        let r = Runfiles::create_for(&binary_path);
+        unsafe { std::env::remove_var("RUNFILES_MANIFEST_FILE") };
+        fs::remove_dir_all(&dir).unwrap();
+
+        let r = r.unwrap();
+        assert_eq!(r.directory, Some(runfiles_dir));
+        assert_eq!(r.manifest, None);
+        assert_eq!(r.manifest_path, None);
+    }
+
+    #[test]
+    fn test_create_for_falls_back_to_directory_when_manifest_file_is_malformed() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = scratch_dir("create_malformed_manifest");
+        let binary_path = dir.join("my_binary");
+        File::create(&binary_path).unwrap();
+        let mut runfiles_name = binary_path.file_name().unwrap().to_owned();
+        runfiles_name.push(".runfiles");
+        let runfiles_dir = binary_path.with_file_name(&runfiles_name);
+        fs::create_dir_all(&runfiles_dir).unwrap();
+
+        let manifest_file = dir.join("MANIFEST_FILE");
+        let mut f = File::create(&manifest_file).unwrap();
+        writeln!(f, "no_separator_in_this_line").unwrap();
+
+        unsafe { std::env::set_var("RUNFILES_MANIFEST_FILE", &manifest_file) };
+        let r = Runfiles::create_for(&binary_path);
+        unsafe { std::env::remove_var("RUNFILES_MANIFEST_FILE") };
+        fs::remove_dir_all(&dir).unwrap();
+
+        let r = r.unwrap();
+        assert_eq!(r.directory, Some(runfiles_dir));
+        assert_eq!(r.manifest, None);
+        assert_eq!(r.manifest_path, None);
+    }
+
+    #[test]
+    fn test_find_runfiles_dir_prefers_env_var_over_argv0_walk() {
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = scratch_dir("runfiles_dir_env");
+        let binary_path = dir.join("binary_with_no_sibling_runfiles");
+        File::create(&binary_path).unwrap();
+
+        unsafe { std::env::set_var("RUNFILES_DIR", &dir) };
+        let found = find_runfiles_dir_for(&binary_path);
+        unsafe { std::env::remove_var("RUNFILES_DIR") };
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found.unwrap(), dir);
+    }
 }